@@ -0,0 +1,64 @@
+//! A lazily-populated in-memory snapshot of the three named channels
+//! (`stable`/`beta`/`nightly`), so `/` and `/api/v1/named_channels` don't pay
+//! for a multi-join DB query on every request. Populated on first read, and
+//! dropped whenever the ingestion subsystem writes new data so the next read
+//! picks up the change.
+
+use std::sync::RwLock;
+
+use crate::db::{self, Pool, VersionQueries};
+use crate::errors::AppError;
+use crate::models::{RustChannelStore, RustVersion};
+
+pub struct ChannelCache {
+    inner: RwLock<Option<RustChannelStore>>,
+}
+
+impl ChannelCache {
+    pub fn new() -> Self {
+        Self {
+            inner: RwLock::new(None),
+        }
+    }
+
+    /// Return the cached snapshot, repopulating it from the DB on a miss.
+    pub async fn get_or_populate(&self, pool: &Pool) -> Result<RustChannelStore, AppError> {
+        if let Some(store) = self.inner.read().unwrap().clone() {
+            return Ok(store);
+        }
+        self.populate(pool).await
+    }
+
+    async fn populate(&self, pool: &Pool) -> Result<RustChannelStore, AppError> {
+        let store = RustChannelStore {
+            stable: fetch_named_channel(pool, "stable").await?,
+            beta: fetch_named_channel(pool, "beta").await?,
+            nightly: fetch_named_channel(pool, "nightly").await?,
+        };
+        *self.inner.write().unwrap() = Some(store.clone());
+        Ok(store)
+    }
+
+    /// Drop the cached snapshot; the next read will query the DB and
+    /// repopulate. Call this whenever ingestion writes new rows.
+    pub fn invalidate(&self) {
+        *self.inner.write().unwrap() = None;
+    }
+}
+
+impl Default for ChannelCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn fetch_named_channel(pool: &Pool, channel: &str) -> Result<Option<RustVersion>, AppError> {
+    // A channel with no rows yet (fresh DB, not synced) is a cache miss we
+    // should tolerate rather than fail the whole snapshot over; any other
+    // error (DB down, pool exhausted) should still propagate.
+    match db::execute_versions(pool, VersionQueries::GetVersionInfo, Some(channel.to_string())).await {
+        Ok(versions) => Ok(versions.into_iter().next()),
+        Err(AppError::NotFound(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
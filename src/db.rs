@@ -1,7 +1,8 @@
-use actix_web::{Error, error, web};
-use rusqlite::Statement;
+use actix_web::web;
+use rusqlite::{Statement, params};
 
-use crate::models::{Artefact, ArtefactType, Component, ComponentTarget, RustVersion};
+use crate::errors::AppError;
+use crate::models::{Artefact, ArtefactType, ChannelStatus, Component, ComponentTarget, RustVersion, SyncStatus};
 
 pub type Pool = r2d2::Pool<r2d2_sqlite::SqliteConnectionManager>;
 pub type Connection = r2d2::PooledConnection<r2d2_sqlite::SqliteConnectionManager>;
@@ -15,83 +16,55 @@ pub enum ComponentQueries {
 
 #[allow(clippy::enum_variant_names)]
 pub enum VersionQueries {
-    GetNamedChannels,
     GetAllVersions,
     GetVersionInfo,
 }
 
+pub enum StatusQueries {
+    GetChannelStatuses,
+}
+
 pub async fn execute_components(
     pool: &Pool,
     query: ComponentQueries,
     component: String,
     version: String,
-) -> Result<Component, Error> {
+) -> Result<Component, AppError> {
     let pool = pool.clone();
 
-    let conn = web::block(move || pool.get())
-        .await?
-        .map_err(error::ErrorInternalServerError)?;
+    let conn = web::block(move || pool.get()).await??;
 
     web::block(move || match query {
         ComponentQueries::GetRustComponent => get_rust_component(&conn, &component, &version),
     })
     .await?
-    .map_err(error::ErrorInternalServerError)
 }
 
 pub async fn execute_versions(
     pool: &Pool,
     query: VersionQueries,
     param: Option<String>,
-) -> Result<Vec<RustVersion>, Error> {
+) -> Result<Vec<RustVersion>, AppError> {
     let pool = pool.clone();
 
-    let conn = web::block(move || pool.get())
-        .await?
-        .map_err(error::ErrorInternalServerError)?;
+    let conn = web::block(move || pool.get()).await??;
 
     web::block(move || match query {
-        VersionQueries::GetNamedChannels => get_named_channels(&conn),
         VersionQueries::GetAllVersions => get_all_versions(&conn),
         VersionQueries::GetVersionInfo => get_version_info(&conn, param),
     })
     .await?
-    .map_err(error::ErrorInternalServerError)
 }
 
-fn get_named_channels(conn: &Connection) -> RustVersionsAggResult {
-    let stmt = conn.prepare(
-        "SELECT
-            version, release_date, latest_stable, latest_beta, latest_nightly
-        FROM
-            rust_versions
-        WHERE
-            latest_stable = 1 OR latest_beta = 1 OR latest_nightly = 1
-        ORDER BY
-            release_date
-        DESC LIMIT 3",
-    )?;
+pub async fn execute_status(pool: &Pool, query: StatusQueries) -> Result<Vec<ChannelStatus>, AppError> {
+    let pool = pool.clone();
 
-    get_named_channel_rows(stmt)
-}
+    let conn = web::block(move || pool.get()).await??;
 
-fn get_named_channel_rows(mut statement: Statement) -> RustVersionsAggResult {
-    statement
-        .query_map([], |row| {
-            Ok(RustVersion {
-                version: row.get("version")?,
-                release_date: row.get("release_date")?,
-                git_commit: None,
-                latest_stable: row.get("latest_stable")?,
-                latest_beta: row.get("latest_beta")?,
-                latest_nightly: row.get("latest_nightly")?,
-                components: vec![],
-                profiles: None,
-                renames: None,
-                artefacts: None,
-            })
-        })
-        .and_then(Iterator::collect)
+    web::block(move || match query {
+        StatusQueries::GetChannelStatuses => get_channel_statuses(&conn),
+    })
+    .await?
 }
 
 fn get_rust_components(conn: &Connection, version: &str) -> ComponentAggResult {
@@ -99,7 +72,7 @@ fn get_rust_components(conn: &Connection, version: &str) -> ComponentAggResult {
         "SELECT
             components.name AS component_name, components.version, components.git_commit, components.profile_complete,
             components.profile_default, components.profile_minimal, targets.name AS target_name, targets.url,
-            targets.hash
+            targets.hash, targets.xz_url, targets.xz_hash
         FROM
             components
         LEFT JOIN
@@ -129,6 +102,8 @@ fn get_version_components_rows(mut statement: Statement, version: &str) -> Compo
                     name: name.unwrap_or_default(),
                     url: url.unwrap_or_default(),
                     hash: hash.unwrap_or_default(),
+                    xz_url: row.get("xz_url")?,
+                    xz_hash: row.get("xz_hash")?,
                 })
             } else {
                 None
@@ -164,12 +139,12 @@ fn get_version_components_rows(mut statement: Statement, version: &str) -> Compo
     Ok(components_map.into_values().collect())
 }
 
-fn get_rust_component(conn: &Connection, component: &str, version: &str) -> ComponentResult {
+fn get_rust_component(conn: &Connection, component: &str, version: &str) -> Result<Component, AppError> {
     let stmt = conn.prepare(
         "SELECT
             components.name AS component_name, components.version, components.git_commit, components.profile_complete,
             components.profile_default, components.profile_minimal, targets.name AS target_name, targets.url,
-            targets.hash
+            targets.hash, targets.xz_url, targets.xz_hash
         FROM
             components
         LEFT JOIN
@@ -182,7 +157,13 @@ fn get_rust_component(conn: &Connection, component: &str, version: &str) -> Comp
             components.name = ?2",
     )?;
 
-    get_component_rows(stmt, version, component)
+    match get_component_rows(stmt, version, component) {
+        Ok(component) => Ok(component),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Err(AppError::NotFound(format!(
+            "component '{component}' for version '{version}'"
+        ))),
+        Err(e) => Err(e.into()),
+    }
 }
 
 fn get_component_rows(mut statement: Statement, version: &str, component: &str) -> ComponentResult {
@@ -194,6 +175,8 @@ fn get_component_rows(mut statement: Statement, version: &str, component: &str)
                 name: row.get("target_name").unwrap_or_default(),
                 url: row.get("url").unwrap_or_default(),
                 hash: row.get("hash").unwrap_or_default(),
+                xz_url: row.get("xz_url")?,
+                xz_hash: row.get("xz_hash")?,
             };
 
             if let Some(comp) = &mut rust_component {
@@ -221,7 +204,7 @@ fn get_component_rows(mut statement: Statement, version: &str, component: &str)
     rust_component.ok_or_else(|| rusqlite::Error::QueryReturnedNoRows)
 }
 
-fn get_all_versions(conn: &Connection) -> RustVersionsAggResult {
+fn get_all_versions(conn: &Connection) -> Result<Vec<RustVersion>, AppError> {
     let stmt = conn.prepare(
         "SELECT
             version, release_date, latest_stable, latest_beta, latest_nightly
@@ -232,7 +215,7 @@ fn get_all_versions(conn: &Connection) -> RustVersionsAggResult {
         DESC",
     )?;
 
-    get_all_version_rows(stmt)
+    Ok(get_all_version_rows(stmt)?)
 }
 
 fn get_all_version_rows(mut statement: Statement) -> RustVersionsAggResult {
@@ -254,55 +237,48 @@ fn get_all_version_rows(mut statement: Statement) -> RustVersionsAggResult {
         .and_then(Iterator::collect)
 }
 
-fn get_version_info(conn: &Connection, version: Option<String>) -> RustVersionsAggResult {
-    let version_str = version.as_deref().unwrap_or("latest");
+/// Resolve a semver requirement like `^1.74` or `1.70.x` to the newest known
+/// version satisfying it, for callers that pass something other than an
+/// exact version string or a channel keyword. Returns `None` (rather than an
+/// error) when `candidate` isn't a valid requirement, or nothing satisfies
+/// it, so the caller can fall through to its usual "not found" handling.
+fn resolve_semver_range(versions: &[RustVersion], candidate: &str) -> Option<String> {
+    let req = semver::VersionReq::parse(candidate).ok()?;
+
+    versions
+        .iter()
+        .filter_map(|v| semver::Version::parse(&v.version).ok().map(|parsed| (parsed, v)))
+        .filter(|(parsed, _)| req.matches(parsed))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, v)| v.version.clone())
+}
 
-    let query_version = match get_all_versions(conn) {
-        Ok(versions) => match version_str {
-            "latest" | "stable" => versions
-                .iter()
-                .find(|v| v.latest_stable)
-                .map(|v| v.version.clone())
-                .ok_or_else(|| {
-                    rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        "No stable version found",
-                    )))
-                }),
-            "beta" => versions
-                .iter()
-                .find(|v| v.latest_beta)
-                .map(|v| v.version.clone())
-                .ok_or_else(|| {
-                    rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        "No beta version found",
-                    )))
-                }),
-            "nightly" => versions
-                .iter()
-                .find(|v| v.latest_nightly)
-                .map(|v| v.version.clone())
-                .ok_or_else(|| {
-                    rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        "No nightly version found",
-                    )))
-                }),
-            _ => versions
-                .iter()
-                .find(|v| v.version == version_str)
-                .map(|v| v.version.clone())
-                .ok_or_else(|| {
-                    rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        "Version not found",
-                    )))
-                }),
-        },
-        Err(e) => Err(rusqlite::Error::ToSqlConversionFailure(Box::new(
-            std::io::Error::new(std::io::ErrorKind::Other, e),
-        ))),
+fn get_version_info(conn: &Connection, version: Option<String>) -> Result<Vec<RustVersion>, AppError> {
+    let version_str = version.as_deref().unwrap_or("latest");
+    let versions = get_all_versions(conn)?;
+
+    let query_version = match version_str {
+        "latest" | "stable" => versions
+            .iter()
+            .find(|v| v.latest_stable)
+            .map(|v| v.version.clone())
+            .ok_or_else(|| AppError::NotFound("stable version".to_string())),
+        "beta" => versions
+            .iter()
+            .find(|v| v.latest_beta)
+            .map(|v| v.version.clone())
+            .ok_or_else(|| AppError::NotFound("beta version".to_string())),
+        "nightly" => versions
+            .iter()
+            .find(|v| v.latest_nightly)
+            .map(|v| v.version.clone())
+            .ok_or_else(|| AppError::NotFound("nightly version".to_string())),
+        _ => versions
+            .iter()
+            .find(|v| v.version == version_str)
+            .map(|v| v.version.clone())
+            .or_else(|| resolve_semver_range(&versions, version_str))
+            .ok_or_else(|| AppError::NotFound(format!("version '{version_str}'"))),
     }?;
 
     let stmt = conn.prepare(
@@ -311,7 +287,9 @@ fn get_version_info(conn: &Connection, version: Option<String>) -> RustVersionsA
             release_date,
             latest_stable,
             latest_beta,
-            latest_nightly
+            latest_nightly,
+            profiles,
+            renames
         FROM
             rust_versions
         WHERE
@@ -347,6 +325,8 @@ fn get_version_info(conn: &Connection, version: Option<String>) -> RustVersionsA
 fn get_version_info_rows(mut statement: Statement, version: &String) -> RustVersionsAggResult {
     statement
         .query_map([version], |row| {
+            let profiles_json: Option<String> = row.get("profiles")?;
+            let renames_json: Option<String> = row.get("renames")?;
             Ok(RustVersion {
                 version: row.get("version")?,
                 release_date: row.get("release_date")?,
@@ -355,8 +335,8 @@ fn get_version_info_rows(mut statement: Statement, version: &String) -> RustVers
                 latest_beta: row.get("latest_beta")?,
                 latest_nightly: row.get("latest_nightly")?,
                 components: vec![],
-                profiles: None,
-                renames: None,
+                profiles: profiles_json.and_then(|json| serde_json::from_str(&json).ok()),
+                renames: renames_json.and_then(|json| serde_json::from_str(&json).ok()),
                 artefacts: None,
             })
         })
@@ -426,3 +406,97 @@ fn get_rust_commit_hash_row(
         Err(rusqlite::Error::QueryReturnedNoRows)
     }
 }
+
+/// Record one ingestion attempt. Called by the sync subsystem on every run,
+/// success or failure, so `/api/v1/status` has something to report.
+pub(crate) fn insert_sync_run(
+    conn: &Connection,
+    channel: &str,
+    started_at: &str,
+    status: &SyncStatus,
+    manifest_date: Option<&str>,
+    error: Option<&str>,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO sync_runs (channel, started_at, manifest_date, status, error) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![channel, started_at, manifest_date, status.to_string(), error],
+    )?;
+    Ok(())
+}
+
+/// A channel is stale if it's gone more than twice the periodic sync
+/// interval (`sync::spawn_periodic` re-syncs hourly, see `main.rs`) without a
+/// successful ingest - long enough that a single slow fetch isn't a false
+/// positive, short enough to flag a genuinely broken upstream.
+const STALE_THRESHOLD_SECONDS: i64 = 2 * 60 * 60;
+
+/// Seconds since `last_success_at`, and whether that makes the channel
+/// stale. A channel with no recorded success is always stale.
+fn staleness(last_success_at: Option<&str>) -> (Option<i64>, bool) {
+    match last_success_at.and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok()) {
+        Some(last_success) => {
+            let age = (chrono::Utc::now() - last_success.with_timezone(&chrono::Utc)).num_seconds();
+            (Some(age), age > STALE_THRESHOLD_SECONDS)
+        }
+        None => (None, true),
+    }
+}
+
+fn get_channel_statuses(conn: &Connection) -> Result<Vec<ChannelStatus>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT channel, started_at, manifest_date, status, error
+         FROM sync_runs
+         ORDER BY started_at DESC",
+    )?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>("channel")?,
+                row.get::<_, String>("started_at")?,
+                row.get::<_, String>("status")?,
+                row.get::<_, Option<String>>("error")?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    let mut by_channel: std::collections::HashMap<String, ChannelStatus> = std::collections::HashMap::new();
+
+    // Rows are newest-first, so the first row seen for a channel is its
+    // latest attempt, and the first `success` row seen is its latest
+    // successful sync.
+    for (channel, started_at, status_str, error) in rows {
+        let status = SyncStatus::try_from(status_str.as_str()).ok();
+
+        let entry = by_channel.entry(channel.clone()).or_insert_with(|| ChannelStatus {
+            channel: channel.clone(),
+            last_success_at: None,
+            last_attempt_at: None,
+            last_status: None,
+            last_error: None,
+            stale_for_seconds: None,
+            is_stale: true,
+        });
+
+        if entry.last_attempt_at.is_none() {
+            entry.last_attempt_at = Some(started_at.clone());
+            entry.last_status = status.clone();
+            if status != Some(SyncStatus::Success) {
+                entry.last_error = error;
+            }
+        }
+
+        if status == Some(SyncStatus::Success) && entry.last_success_at.is_none() {
+            entry.last_success_at = Some(started_at);
+        }
+    }
+
+    let mut statuses: Vec<ChannelStatus> = by_channel.into_values().collect();
+    for status in &mut statuses {
+        let (stale_for_seconds, is_stale) = staleness(status.last_success_at.as_deref());
+        status.stale_for_seconds = stale_for_seconds;
+        status.is_stale = is_stale;
+    }
+    statuses.sort_by(|a, b| a.channel.cmp(&b.channel));
+    Ok(statuses)
+}
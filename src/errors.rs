@@ -0,0 +1,57 @@
+//! A crate-wide error type so handlers can return `Result<_, AppError>`
+//! instead of `.unwrap()`-ing DB results and template renders. Implements
+//! `ResponseError` so errors carry the right status code instead of
+//! panicking the worker; on the HTML routes the existing `render_error_page`
+//! error handler (wired in `main.rs` via `ErrorHandlers`) renders them
+//! through `error.tera`.
+
+use actix_web::{HttpResponse, ResponseError, error::BlockingError, http::StatusCode};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error("{0} not found")]
+    NotFound(String),
+
+    #[error("database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    #[error("connection pool error: {0}")]
+    Pool(#[from] r2d2::Error),
+
+    #[error("template error: {0}")]
+    Template(#[from] tera::Error),
+
+    #[error("upstream error: {0}")]
+    Upstream(String),
+
+    #[error("internal error: {0}")]
+    Internal(String),
+}
+
+impl From<BlockingError> for AppError {
+    fn from(e: BlockingError) -> Self {
+        AppError::Internal(e.to_string())
+    }
+}
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Database(_) | AppError::Pool(_) | AppError::Template(_) | AppError::Internal(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            AppError::Upstream(_) => StatusCode::BAD_GATEWAY,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        // On the HTML routes `render_error_page` (an `ErrorHandlers`
+        // middleware in `main.rs`) rewrites this body into the `error.tera`
+        // page once it sees the status code, same as it already does for
+        // `actix_web::error::Error`s raised elsewhere. The API routes sit
+        // outside that middleware and return this plain-text body as is.
+        HttpResponse::build(self.status_code()).body(self.to_string())
+    }
+}
@@ -4,7 +4,6 @@ use actix_web::{
     HttpRequest,
     HttpResponse,
     HttpServer,
-    Responder,
     Result,
     dev::ServiceResponse,
     error, // Added error module
@@ -19,10 +18,18 @@ use oasgen::{Server, oasgen};
 use tera::{Context, Tera};
 
 mod db;
-use db::{ComponentQueries, Pool, VersionQueries};
+use db::{ComponentQueries, Pool, StatusQueries, VersionQueries};
 
 pub mod models;
-use models::{Component, RustVersion};
+use models::{ChannelStatus, Component, RustVersion};
+
+mod sync;
+mod migrator;
+mod cache;
+use cache::ChannelCache;
+mod errors;
+use errors::AppError;
+mod manifest;
 
 // --- Error Pages ---
 
@@ -95,17 +102,21 @@ fn render_error_page<B>(res: ServiceResponse<B>) -> Result<ErrorHandlerResponse<
 // --- HTML Rendering Handlers ---
 
 #[get("/")]
-pub async fn hello(tera: Data<Tera>, db: web::Data<Pool>) -> impl Responder {
+pub async fn hello(
+    tera: Data<Tera>,
+    db: web::Data<Pool>,
+    cache: web::Data<ChannelCache>,
+) -> Result<HttpResponse, AppError> {
     let mut ctx = Context::new();
-    let versions = db::execute_versions(&db, VersionQueries::GetAllVersions, None)
-        .await
-        .unwrap();
+    let versions = db::execute_versions(&db, VersionQueries::GetAllVersions, None).await?;
     ctx.insert("versions", &versions);
-    let named_channels = db::execute_versions(&db, VersionQueries::GetNamedChannels, None)
-        .await
-        .unwrap();
+    let store = cache.get_or_populate(&db).await?;
+    let named_channels: Vec<RustVersion> = [store.stable, store.beta, store.nightly]
+        .into_iter()
+        .flatten()
+        .collect();
     ctx.insert("named_channels", &named_channels);
-    HttpResponse::Ok().body(tera.render("index.tera", &ctx).unwrap())
+    Ok(HttpResponse::Ok().body(tera.render("index.tera", &ctx)?))
 }
 
 #[get("/info/{version}")]
@@ -113,27 +124,23 @@ pub async fn versioninfo(
     tera: Data<Tera>,
     path: web::Path<String>,
     db: web::Data<Pool>,
-) -> impl Responder {
+) -> Result<HttpResponse, AppError> {
     let mut ctx = Context::new();
-    let rustversion =
-        db::execute_versions(&db, VersionQueries::GetVersionInfo, Some(path.to_string()))
-            .await
-            .unwrap()
-            .into_iter()
-            .next()
-            .unwrap();
+    let rustversion = db::execute_versions(&db, VersionQueries::GetVersionInfo, Some(path.to_string()))
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::NotFound(format!("version '{}'", path.as_str())))?;
     ctx.insert("version", &rustversion);
-    HttpResponse::Ok().body(tera.render("versioninfo.tera", &ctx).unwrap())
+    Ok(HttpResponse::Ok().body(tera.render("versioninfo.tera", &ctx)?))
 }
 
 #[get("/info/all")]
-pub async fn allversions(tera: Data<Tera>, db: web::Data<Pool>) -> impl Responder {
+pub async fn allversions(tera: Data<Tera>, db: web::Data<Pool>) -> Result<HttpResponse, AppError> {
     let mut ctx = Context::new();
-    let versions = db::execute_versions(&db, VersionQueries::GetAllVersions, None)
-        .await
-        .unwrap();
+    let versions = db::execute_versions(&db, VersionQueries::GetAllVersions, None).await?;
     ctx.insert("versions", &versions);
-    HttpResponse::Ok().body(tera.render("allversions.tera", &ctx).unwrap())
+    Ok(HttpResponse::Ok().body(tera.render("allversions.tera", &ctx)?))
 }
 
 #[get("/info/component/{name}/{version}")]
@@ -141,7 +148,7 @@ pub async fn component(
     tera: Data<Tera>,
     path: web::Path<(String, String)>,
     db: web::Data<Pool>,
-) -> impl Responder {
+) -> Result<HttpResponse, AppError> {
     let mut ctx = Context::new();
     let component = db::execute_components(
         &db,
@@ -149,11 +156,18 @@ pub async fn component(
         path.0.to_string(),
         path.1.to_string(),
     )
-    .await
-    .unwrap();
+    .await?;
     ctx.insert("rustversion", &path.1.to_string());
     ctx.insert("component", &component);
-    HttpResponse::Ok().body(tera.render("component.tera", &ctx).unwrap())
+    Ok(HttpResponse::Ok().body(tera.render("component.tera", &ctx)?))
+}
+
+#[get("/status")]
+pub async fn statuspage(tera: Data<Tera>, db: web::Data<Pool>) -> Result<HttpResponse, AppError> {
+    let mut ctx = Context::new();
+    let statuses = db::execute_status(&db, StatusQueries::GetChannelStatuses).await?;
+    ctx.insert("statuses", &statuses);
+    Ok(HttpResponse::Ok().body(tera.render("status.tera", &ctx)?))
 }
 
 // --- API Handlers ---
@@ -162,13 +176,13 @@ pub async fn component(
 pub async fn versioninfoapi(
     path: web::Path<String>,
     db: web::Data<Pool>,
-) -> Result<web::Json<RustVersion>, Box<dyn std::error::Error>> {
+) -> Result<web::Json<RustVersion>, AppError> {
     let version_str = path.into_inner();
-    let rustversion = db::execute_versions(&db, VersionQueries::GetVersionInfo, Some(version_str))
+    let rustversion = db::execute_versions(&db, VersionQueries::GetVersionInfo, Some(version_str.clone()))
         .await?
         .into_iter()
         .next()
-        .ok_or_else(|| format!("Version not found"))?;
+        .ok_or_else(|| AppError::NotFound(format!("version '{version_str}'")))?;
     Ok(web::Json(rustversion))
 }
 
@@ -176,7 +190,7 @@ pub async fn versioninfoapi(
 pub async fn componentinfoapi(
     path: web::Path<(String, String)>,
     db: web::Data<Pool>,
-) -> Result<web::Json<Vec<Component>>, Box<dyn std::error::Error>> {
+) -> Result<web::Json<Vec<Component>>, AppError> {
     let (name, version) = path.into_inner();
     let rust_component =
         db::execute_components(&db, ComponentQueries::GetRustComponent, name, version).await?;
@@ -186,11 +200,37 @@ pub async fn componentinfoapi(
 #[oasgen]
 pub async fn namedchannelsapi(
     db: web::Data<Pool>,
-) -> Result<web::Json<Vec<RustVersion>>, Box<dyn std::error::Error>> {
-    let named_channels = db::execute_versions(&db, VersionQueries::GetNamedChannels, None).await?;
+    cache: web::Data<ChannelCache>,
+) -> Result<web::Json<Vec<RustVersion>>, AppError> {
+    let store = cache.get_or_populate(&db).await?;
+    let named_channels: Vec<RustVersion> = [store.stable, store.beta, store.nightly]
+        .into_iter()
+        .flatten()
+        .collect();
     Ok(web::Json(named_channels))
 }
 
+#[oasgen]
+pub async fn statusapi(db: web::Data<Pool>) -> Result<web::Json<Vec<ChannelStatus>>, AppError> {
+    let statuses = db::execute_status(&db, StatusQueries::GetChannelStatuses).await?;
+    Ok(web::Json(statuses))
+}
+
+#[oasgen]
+pub async fn versionmanifest(
+    path: web::Path<String>,
+    db: web::Data<Pool>,
+) -> Result<HttpResponse, AppError> {
+    let version_str = path.into_inner();
+    let rustversion = db::execute_versions(&db, VersionQueries::GetVersionInfo, Some(version_str.clone()))
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::NotFound(format!("version '{version_str}'")))?;
+    let body = manifest::render(&rustversion)?;
+    Ok(HttpResponse::Ok().content_type("text/plain").body(body))
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
@@ -198,14 +238,51 @@ async fn main() -> std::io::Result<()> {
     let log_format = "%{r}a %U %D %b %s";
     let tera = Data::new(Tera::new("./templates/*").unwrap());
 
-    let manager = r2d2_sqlite::SqliteConnectionManager::file("rust_versions.sqlite3");
+    const DB_PATH: &str = "rust_versions.sqlite3";
+    migrator::run(&mut rusqlite::Connection::open(DB_PATH).unwrap()).expect("schema migration failed");
+
+    let manager = r2d2_sqlite::SqliteConnectionManager::file(DB_PATH);
     let pool = r2d2::Pool::new(manager).unwrap();
+
+    // `cargo run -- sync` does a one-shot manifest ingestion and exits,
+    // instead of booting the web server; handy for cron/CI or a first-run
+    // populate before anyone hits the site. `cargo run -- sync-dated <date>
+    // <version>` does the same for a single archived manifest, e.g. for
+    // backfilling history.
+    let channel_cache = Data::new(ChannelCache::new());
+
+    let mut cli_args = std::env::args().skip(1);
+    match cli_args.next().as_deref() {
+        Some("sync") => {
+            sync::sync_all(&pool, &channel_cache)
+                .await
+                .expect("manifest sync failed");
+            return Ok(());
+        }
+        Some("sync-dated") => {
+            let date = cli_args.next().expect("usage: sync-dated <date> <version>");
+            let version = cli_args.next().expect("usage: sync-dated <date> <version>");
+            sync::sync_dated_manifest(&pool, &channel_cache, &date, &version)
+                .await
+                .expect("manifest sync failed");
+            return Ok(());
+        }
+        _ => {}
+    }
+
     let pool_data = web::Data::new(pool.clone());
+    sync::spawn_periodic(
+        pool.clone(),
+        channel_cache.clone(),
+        std::time::Duration::from_secs(60 * 60),
+    );
 
     let oasgen_server = Server::actix() // Use default Server builder
         .get("/api/v1/version/{version}", versioninfoapi)
         .get("/api/v1/component/{name}/{version}", componentinfoapi)
         .get("/api/v1/named_channels", namedchannelsapi)
+        .get("/api/v1/status", statusapi)
+        .get("/api/v1/version/{version}/manifest.toml", versionmanifest)
         .route_json_spec("/openapi.json")
         .swagger_ui("/swagger-ui/") // Must have a trailing slash
         .freeze();
@@ -213,24 +290,38 @@ async fn main() -> std::io::Result<()> {
     HttpServer::new(move || {
         let error_handlers = ErrorHandlers::new()
             .handler(StatusCode::BAD_REQUEST, render_error_page)           // 400
+            .handler(StatusCode::NOT_FOUND, render_error_page)             // 404
             .handler(StatusCode::INTERNAL_SERVER_ERROR, render_error_page) // 500
             .handler(StatusCode::METHOD_NOT_ALLOWED, render_error_page)   // 405
             .handler(StatusCode::NOT_IMPLEMENTED, render_error_page)        // 501
+            .handler(StatusCode::BAD_GATEWAY, render_error_page)           // 502
             .handler(StatusCode::SERVICE_UNAVAILABLE, render_error_page)   // 503
         ;
         App::new()
             .app_data(pool_data.clone())
             .app_data(tera.clone())
+            .app_data(channel_cache.clone())
             .wrap(middleware::Logger::new(log_format))
-            .wrap(error_handlers)
-            // Mount non-API routes
-            .service(hello)
-            .service(versioninfo)
-            .service(allversions)
-            .service(component)
             .service(Files::new("/static", "./static"))
             // Mount oasgen managed services
             .service(oasgen_server.clone().into_service())
+            // The Tera-rendered error page only makes sense for the HTML
+            // routes below - scope it to them so /api/v1/* (mounted above,
+            // outside this scope) keeps returning AppError's own plain-text
+            // body on a 404/502/etc instead of an HTML page. The empty
+            // prefix matches every path, so this scope goes after the API
+            // and static services. Unmatched paths also enter it and reach
+            // `default_service` below through this middleware, so the
+            // `not_found` page is rendered a second time (without the path).
+            .service(
+                web::scope("")
+                    .wrap(error_handlers)
+                    .service(hello)
+                    .service(versioninfo)
+                    .service(allversions)
+                    .service(component)
+                    .service(statuspage),
+            )
             .default_service(web::route().to(not_found)) // Catch-all for non-API routes; display 404
     })
     .bind(("0.0.0.0", 8080))?
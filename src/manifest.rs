@@ -0,0 +1,109 @@
+//! Reconstructs Rust's v2 channel manifest TOML (the same shape `sync.rs`
+//! parses on ingestion) from a fully-resolved `RustVersion`, so an archived
+//! version's manifest can be fetched back out even once it's no longer the
+//! live channel pointer upstream.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::errors::AppError;
+use crate::models::RustVersion;
+
+#[derive(Serialize)]
+struct ManifestDocument {
+    #[serde(rename = "manifest-version")]
+    manifest_version: String,
+    date: String,
+    pkg: HashMap<String, PkgEntry>,
+    profiles: HashMap<String, Vec<String>>,
+    renames: HashMap<String, RenameEntry>,
+}
+
+#[derive(Serialize)]
+struct PkgEntry {
+    version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    git_commit_hash: Option<String>,
+    target: HashMap<String, TargetEntry>,
+}
+
+#[derive(Serialize)]
+struct TargetEntry {
+    available: bool,
+    url: String,
+    hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    xz_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    xz_hash: Option<String>,
+}
+
+#[derive(Serialize)]
+struct RenameEntry {
+    to: String,
+}
+
+/// Render `version` back into a `channel-rust-<version>.toml` document.
+pub fn render(version: &RustVersion) -> Result<String, AppError> {
+    let mut pkg = HashMap::new();
+    let mut profiles: HashMap<String, Vec<String>> = HashMap::new();
+
+    for component in &version.components {
+        let target = component
+            .target
+            .iter()
+            .flatten()
+            .map(|t| {
+                (
+                    t.name.clone(),
+                    TargetEntry {
+                        available: true,
+                        url: t.url.clone(),
+                        hash: t.hash.clone(),
+                        xz_url: t.xz_url.clone(),
+                        xz_hash: t.xz_hash.clone(),
+                    },
+                )
+            })
+            .collect();
+
+        pkg.insert(
+            component.name.clone(),
+            PkgEntry {
+                version: component.version.clone(),
+                git_commit_hash: component.git_commit.clone(),
+                target,
+            },
+        );
+
+        for (flag, profile) in [
+            (component.profile_complete, "complete"),
+            (component.profile_default, "default"),
+            (component.profile_minimal, "minimal"),
+        ] {
+            if flag {
+                profiles.entry(profile.to_string()).or_default().push(component.name.clone());
+            }
+        }
+    }
+
+    let renames = version
+        .renames
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(from, to)| (from, RenameEntry { to }))
+        .collect();
+
+    let document = ManifestDocument {
+        manifest_version: "2".to_string(),
+        date: version.release_date.clone(),
+        pkg,
+        profiles,
+        renames,
+    };
+
+    toml::to_string_pretty(&document)
+        .map_err(|e| AppError::Upstream(format!("failed to render manifest: {e}")))
+}
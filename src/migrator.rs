@@ -0,0 +1,100 @@
+//! A tiny embedded migrator: an ordered list of SQL steps, tracked via
+//! `PRAGMA user_version`, applied once at startup before the pool is handed
+//! to actix. This is what makes the service self-bootstrapping instead of
+//! assuming `rust_versions.sqlite3` already has a schema.
+
+use rusqlite::Connection;
+
+/// One migration step. Migrations are applied in array order starting from
+/// the database's current `user_version`; the index of a step in [`MIGRATIONS`]
+/// (1-based) is its target schema version.
+struct Migration {
+    description: &'static str,
+    sql: &'static str,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        description: "create rust_versions, components, targets, artefacts",
+        sql: "
+            CREATE TABLE rust_versions (
+                version         TEXT PRIMARY KEY,
+                release_date    TEXT NOT NULL,
+                latest_stable   INTEGER NOT NULL DEFAULT 0,
+                latest_beta     INTEGER NOT NULL DEFAULT 0,
+                latest_nightly  INTEGER NOT NULL DEFAULT 0,
+                profiles        TEXT,
+                renames         TEXT
+            );
+
+            CREATE TABLE components (
+                id                  INTEGER PRIMARY KEY,
+                rust_version        TEXT NOT NULL REFERENCES rust_versions(version),
+                name                TEXT NOT NULL,
+                version             TEXT NOT NULL,
+                git_commit          TEXT,
+                profile_complete    INTEGER NOT NULL DEFAULT 0,
+                profile_default     INTEGER NOT NULL DEFAULT 0,
+                profile_minimal     INTEGER NOT NULL DEFAULT 0,
+                UNIQUE(rust_version, name)
+            );
+
+            CREATE TABLE targets (
+                id              INTEGER PRIMARY KEY,
+                component_id    INTEGER NOT NULL REFERENCES components(id),
+                name            TEXT NOT NULL,
+                url             TEXT NOT NULL,
+                hash            TEXT NOT NULL
+            );
+
+            CREATE TABLE artefacts (
+                id              INTEGER PRIMARY KEY,
+                rust_version    TEXT NOT NULL REFERENCES rust_versions(version),
+                type            INTEGER NOT NULL,
+                url             TEXT NOT NULL,
+                hash            TEXT NOT NULL
+            );
+        ",
+    },
+    Migration {
+        description: "create sync_runs",
+        sql: "
+            CREATE TABLE sync_runs (
+                id              INTEGER PRIMARY KEY,
+                channel         TEXT NOT NULL,
+                started_at      TEXT NOT NULL,
+                manifest_date   TEXT,
+                status          TEXT NOT NULL,
+                error           TEXT
+            );
+
+            CREATE INDEX idx_sync_runs_channel_started_at ON sync_runs (channel, started_at DESC);
+        ",
+    },
+    Migration {
+        description: "add xz_url/xz_hash to targets",
+        sql: "
+            ALTER TABLE targets ADD COLUMN xz_url TEXT;
+            ALTER TABLE targets ADD COLUMN xz_hash TEXT;
+        ",
+    },
+];
+
+/// Run every migration newer than the database's current `user_version`,
+/// inside a single transaction, and leave `user_version` pointing at the
+/// newest applied step.
+pub fn run(conn: &mut Connection) -> rusqlite::Result<()> {
+    let current_version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    let tx = conn.transaction()?;
+    for (i, migration) in MIGRATIONS.iter().enumerate() {
+        let target_version = (i + 1) as i64;
+        if target_version <= current_version {
+            continue;
+        }
+        log::info!("applying migration {target_version}: {}", migration.description);
+        tx.execute_batch(migration.sql)?;
+        tx.execute_batch(&format!("PRAGMA user_version = {target_version}"))?;
+    }
+    tx.commit()
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum ArtefactType {
     InstallerMSI = 1,
     InstallerPkg = 2,
@@ -26,7 +26,7 @@ impl From<ArtefactType> for i32 {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Artefact {
     pub artefact_type: ArtefactType,
     pub url: String,
@@ -38,6 +38,8 @@ pub struct ComponentTarget {
     pub name: String,
     pub url: String,
     pub hash: String,
+    pub xz_url: Option<String>,
+    pub xz_hash: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -51,7 +53,7 @@ pub struct Component {
     pub profile_minimal: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RustVersion {
     pub version: String,
     pub release_date: String,
@@ -64,8 +66,62 @@ pub struct RustVersion {
     pub artefacts: Option<Vec<Artefact>>,
 }
 
+#[derive(Debug, Clone)]
 pub struct RustChannelStore {
     pub stable: Option<RustVersion>,
     pub beta: Option<RustVersion>,
     pub nightly: Option<RustVersion>,
 }
+
+/// Mirrors how docs.rs records a build status per build: one row per
+/// ingestion attempt, so operators can tell a failing upstream fetch apart
+/// from a DB that's merely gone a while without a fresh sync.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum SyncStatus {
+    Success,
+    Failure,
+    Partial,
+}
+
+impl std::fmt::Display for SyncStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            SyncStatus::Success => "success",
+            SyncStatus::Failure => "failure",
+            SyncStatus::Partial => "partial",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl TryFrom<&str> for SyncStatus {
+    type Error = &'static str;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "success" => Ok(SyncStatus::Success),
+            "failure" => Ok(SyncStatus::Failure),
+            "partial" => Ok(SyncStatus::Partial),
+            _ => Err("Invalid value for SyncStatus"),
+        }
+    }
+}
+
+/// Per-channel freshness summary surfaced by `GET /api/v1/status`: the last
+/// successful sync, the last attempt (which may have failed), and that
+/// attempt's error, plus a server-computed staleness so consumers don't have
+/// to re-derive it from an RFC3339 timestamp themselves.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChannelStatus {
+    pub channel: String,
+    pub last_success_at: Option<String>,
+    pub last_attempt_at: Option<String>,
+    pub last_status: Option<SyncStatus>,
+    pub last_error: Option<String>,
+    /// Seconds since `last_success_at`, or `None` if the channel has never
+    /// synced successfully.
+    pub stale_for_seconds: Option<i64>,
+    /// `true` if the channel has never synced successfully, or its last
+    /// success is older than the staleness threshold.
+    pub is_stale: bool,
+}
@@ -0,0 +1,422 @@
+//! Ingests Rust's official v2 channel manifests and upserts them into the
+//! SQLite store, so `db.rs` has something other than hand-populated rows to
+//! read.
+//!
+//! Manifests live at `https://static.rust-lang.org/dist/channel-rust-<channel>.toml`
+//! for the three rolling channels, and at
+//! `https://static.rust-lang.org/dist/<date>/channel-rust-<version>.toml` for
+//! an archived snapshot. Both shapes are the same TOML document, so one
+//! parser covers both.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use log::{error, info, warn};
+use rusqlite::params;
+use serde::Deserialize;
+
+use crate::cache::ChannelCache;
+use crate::db::{self, Connection, Pool};
+use crate::models::{ArtefactType, SyncStatus};
+
+const DIST_BASE_URL: &str = "https://static.rust-lang.org/dist";
+const CHANNELS: [&str; 3] = ["stable", "beta", "nightly"];
+
+#[derive(Debug)]
+pub enum SyncError {
+    Http(reqwest::Error),
+    Toml(toml::de::Error),
+    Database(rusqlite::Error),
+    Pool(r2d2::Error),
+    BlockingTask(actix_web::error::BlockingError),
+    MalformedManifest(String),
+}
+
+impl std::fmt::Display for SyncError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncError::Http(e) => write!(f, "failed to fetch manifest: {e}"),
+            SyncError::Toml(e) => write!(f, "failed to parse manifest: {e}"),
+            SyncError::Database(e) => write!(f, "database error during sync: {e}"),
+            SyncError::Pool(e) => write!(f, "failed to get a pooled connection: {e}"),
+            SyncError::BlockingTask(e) => write!(f, "blocking sync task failed: {e}"),
+            SyncError::MalformedManifest(msg) => write!(f, "malformed manifest: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for SyncError {}
+
+impl From<reqwest::Error> for SyncError {
+    fn from(e: reqwest::Error) -> Self {
+        SyncError::Http(e)
+    }
+}
+
+impl From<toml::de::Error> for SyncError {
+    fn from(e: toml::de::Error) -> Self {
+        SyncError::Toml(e)
+    }
+}
+
+impl From<rusqlite::Error> for SyncError {
+    fn from(e: rusqlite::Error) -> Self {
+        SyncError::Database(e)
+    }
+}
+
+impl From<r2d2::Error> for SyncError {
+    fn from(e: r2d2::Error) -> Self {
+        SyncError::Pool(e)
+    }
+}
+
+impl From<actix_web::error::BlockingError> for SyncError {
+    fn from(e: actix_web::error::BlockingError) -> Self {
+        SyncError::BlockingTask(e)
+    }
+}
+
+// --- Manifest document shape (Rust's "v2" channel manifest format) ---
+
+#[derive(Debug, Deserialize)]
+struct ManifestDocument {
+    date: String,
+    pkg: HashMap<String, PkgEntry>,
+    #[serde(default)]
+    renames: HashMap<String, RenameEntry>,
+    #[serde(default)]
+    profiles: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PkgEntry {
+    version: String,
+    git_commit_hash: Option<String>,
+    #[serde(default)]
+    target: HashMap<String, TargetEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TargetEntry {
+    available: bool,
+    url: Option<String>,
+    hash: Option<String>,
+    xz_url: Option<String>,
+    xz_hash: Option<String>,
+    #[serde(default)]
+    components: Vec<ComponentRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ComponentRef {
+    pkg: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RenameEntry {
+    to: String,
+}
+
+/// Fetch and upsert a single channel (`stable`, `beta`, or `nightly`).
+pub async fn sync_channel(pool: &Pool, cache: &ChannelCache, channel: &str) -> Result<(), SyncError> {
+    let url = format!("{DIST_BASE_URL}/channel-rust-{channel}.toml");
+    sync_manifest_url(pool, cache, &url, channel).await
+}
+
+/// Fetch and upsert an archived manifest for a specific date, e.g. for
+/// backfilling history rather than tracking the live channel pointer.
+pub async fn sync_dated_manifest(
+    pool: &Pool,
+    cache: &ChannelCache,
+    date: &str,
+    version: &str,
+) -> Result<(), SyncError> {
+    let url = format!("{DIST_BASE_URL}/{date}/channel-rust-{version}.toml");
+    sync_manifest_url(pool, cache, &url, version).await
+}
+
+async fn sync_manifest_url(
+    pool: &Pool,
+    cache: &ChannelCache,
+    url: &str,
+    channel: &str,
+) -> Result<(), SyncError> {
+    info!("fetching channel manifest for {channel} from {url}");
+    let started_at = chrono::Utc::now().to_rfc3339();
+    let outcome = fetch_and_upsert(pool, url, channel).await;
+
+    let (status, manifest_date, error_message) = match &outcome {
+        Ok((date, failed_components)) if failed_components.is_empty() => {
+            (SyncStatus::Success, Some(date.clone()), None)
+        }
+        Ok((date, failed_components)) => (
+            SyncStatus::Partial,
+            Some(date.clone()),
+            Some(format!("failed components: {}", failed_components.join(", "))),
+        ),
+        Err(e) => (SyncStatus::Failure, None, Some(e.to_string())),
+    };
+    if let Err(e) = record_sync_run(pool, channel, &started_at, status, manifest_date, error_message).await {
+        warn!("failed to record sync run for {channel}: {e}");
+    }
+
+    // A failed upsert may still have written some rows, so drop the
+    // snapshot whatever the outcome.
+    cache.invalidate();
+    outcome?;
+    Ok(())
+}
+
+async fn fetch_and_upsert(pool: &Pool, url: &str, channel: &str) -> Result<(String, Vec<String>), SyncError> {
+    let body = reqwest::get(url).await?.error_for_status()?.text().await?;
+    let manifest: ManifestDocument = toml::from_str(&body)?;
+    let date = manifest.date.clone();
+
+    let pool = pool.clone();
+    let channel = channel.to_string();
+    let failed_components = web_block(pool, move |conn| upsert_manifest(conn, &channel, &manifest)).await?;
+    Ok((date, failed_components))
+}
+
+async fn record_sync_run(
+    pool: &Pool,
+    channel: &str,
+    started_at: &str,
+    status: SyncStatus,
+    manifest_date: Option<String>,
+    error: Option<String>,
+) -> Result<(), SyncError> {
+    let pool = pool.clone();
+    let channel = channel.to_string();
+    let started_at = started_at.to_string();
+    web_block(pool, move |conn| {
+        db::insert_sync_run(
+            conn,
+            &channel,
+            &started_at,
+            &status,
+            manifest_date.as_deref(),
+            error.as_deref(),
+        )
+        .map_err(SyncError::from)
+    })
+    .await
+}
+
+/// Run `web::block`-style blocking work on a pooled connection from async
+/// context, mirroring how `db::execute_versions` hops onto a blocking thread.
+async fn web_block<F, T>(pool: Pool, f: F) -> Result<T, SyncError>
+where
+    F: FnOnce(&Connection) -> Result<T, SyncError> + Send + 'static,
+    T: Send + 'static,
+{
+    actix_web::web::block(move || {
+        let conn = pool.get()?;
+        f(&conn)
+    })
+    .await?
+}
+
+/// Rust's real channel manifests carry `[pkg.rust].version` as the full
+/// `rustc --version` string, e.g. `"1.78.0 (9b00956e5 2024-04-29)"`, not a
+/// bare semver. Only the leading `X.Y.Z[-channel]` token is stable and
+/// semver-parseable, so that's what we use as `rust_versions.version`: the
+/// primary key that exact-match routes and `resolve_semver_range` compare
+/// against.
+fn version_key(raw: &str) -> &str {
+    raw.split_whitespace().next().unwrap_or(raw)
+}
+
+/// Upsert the manifest, returning the names of any individual components
+/// that failed along the way. A component-level failure doesn't abort the
+/// whole manifest - the rest of the channel's components are still worth
+/// having - but it does mean the ingest as a whole is only partially
+/// successful, which the caller records via [`SyncStatus::Partial`].
+fn upsert_manifest(conn: &Connection, channel: &str, manifest: &ManifestDocument) -> Result<Vec<String>, SyncError> {
+    let rust_pkg = manifest.pkg.get("rust").ok_or_else(|| {
+        SyncError::MalformedManifest("missing top-level [pkg.rust] entry".to_string())
+    })?;
+    let version = version_key(&rust_pkg.version).to_string();
+
+    let profiles_json = serde_json::to_string(&manifest.profiles).ok();
+    let renames: HashMap<String, String> = manifest
+        .renames
+        .iter()
+        .map(|(from, to)| (from.clone(), to.to.clone()))
+        .collect();
+    let renames_json = serde_json::to_string(&renames).ok();
+
+    conn.execute(
+        "INSERT INTO rust_versions (version, release_date, latest_stable, latest_beta, latest_nightly, profiles, renames)
+         VALUES (?1, ?2, 0, 0, 0, ?3, ?4)
+         ON CONFLICT(version) DO UPDATE SET
+            release_date = excluded.release_date,
+            profiles = excluded.profiles,
+            renames = excluded.renames",
+        params![version, manifest.date, profiles_json, renames_json],
+    )?;
+
+    set_latest_channel_flag(conn, channel, &version)?;
+
+    let mut failed_components = Vec::new();
+    for (name, pkg) in &manifest.pkg {
+        if let Err(e) = upsert_component(conn, &version, name, pkg, &manifest.profiles) {
+            warn!("failed to upsert component '{name}' for {channel} ({version}): {e}");
+            failed_components.push(name.clone());
+        }
+    }
+    upsert_artefacts(conn, &version, rust_pkg)?;
+
+    Ok(failed_components)
+}
+
+/// Ensure exactly one row per channel carries the `latest_*` flag: clear the
+/// previous holder, then set it on the version we just ingested.
+fn set_latest_channel_flag(conn: &Connection, channel: &str, version: &str) -> Result<(), SyncError> {
+    let column = match channel {
+        "stable" => "latest_stable",
+        "beta" => "latest_beta",
+        "nightly" => "latest_nightly",
+        // Archived/dated syncs don't move the rolling channel pointer.
+        _ => return Ok(()),
+    };
+
+    conn.execute(&format!("UPDATE rust_versions SET {column} = 0"), [])?;
+    conn.execute(
+        &format!("UPDATE rust_versions SET {column} = 1 WHERE version = ?1"),
+        params![version],
+    )?;
+    Ok(())
+}
+
+fn upsert_component(
+    conn: &Connection,
+    version: &str,
+    name: &str,
+    pkg: &PkgEntry,
+    profiles: &HashMap<String, Vec<String>>,
+) -> Result<(), SyncError> {
+    let in_profile = |profile: &str| {
+        profiles
+            .get(profile)
+            .is_some_and(|members| members.iter().any(|m| m == name))
+    };
+
+    conn.execute(
+        "INSERT INTO components (rust_version, name, version, git_commit, profile_complete, profile_default, profile_minimal)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(rust_version, name) DO UPDATE SET
+            version = excluded.version,
+            git_commit = excluded.git_commit,
+            profile_complete = excluded.profile_complete,
+            profile_default = excluded.profile_default,
+            profile_minimal = excluded.profile_minimal",
+        params![
+            version,
+            name,
+            pkg.version,
+            pkg.git_commit_hash,
+            in_profile("complete"),
+            in_profile("default"),
+            in_profile("minimal"),
+        ],
+    )?;
+
+    let component_id: i64 = conn.query_row(
+        "SELECT id FROM components WHERE rust_version = ?1 AND name = ?2",
+        params![version, name],
+        |row| row.get(0),
+    )?;
+
+    conn.execute("DELETE FROM targets WHERE component_id = ?1", params![component_id])?;
+    for (triple, target) in &pkg.target {
+        if !target.available {
+            continue;
+        }
+        let Some(url) = &target.url else { continue };
+        conn.execute(
+            "INSERT INTO targets (component_id, name, url, hash, xz_url, xz_hash) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                component_id,
+                triple,
+                url,
+                target.hash.clone().unwrap_or_default(),
+                target.xz_url,
+                target.xz_hash,
+            ],
+        )?;
+        if !target.components.is_empty() {
+            // Sub-components listed under `rust`'s target entries describe
+            // which components ship with a given target's default install;
+            // the component rows above already carry per-target availability,
+            // so there's nothing further to record per sub-component here.
+        }
+    }
+
+    Ok(())
+}
+
+fn upsert_artefacts(conn: &Connection, version: &str, rust_pkg: &PkgEntry) -> Result<(), SyncError> {
+    conn.execute("DELETE FROM artefacts WHERE rust_version = ?1", params![version])?;
+
+    for target in rust_pkg.target.values() {
+        if !target.available {
+            continue;
+        }
+        if let (Some(url), Some(hash)) = (&target.url, &target.hash) {
+            let artefact_type = classify_artefact(url);
+            if let Some(artefact_type) = artefact_type {
+                conn.execute(
+                    "INSERT INTO artefacts (rust_version, type, url, hash) VALUES (?1, ?2, ?3, ?4)",
+                    params![version, i32::from(artefact_type), url, hash],
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn classify_artefact(url: &str) -> Option<ArtefactType> {
+    if url.ends_with(".msi") {
+        Some(ArtefactType::InstallerMSI)
+    } else if url.ends_with(".pkg") {
+        Some(ArtefactType::InstallerPkg)
+    } else if url.contains("rustc-") && (url.ends_with(".tar.gz") || url.ends_with(".tar.xz")) {
+        Some(ArtefactType::SourceCode)
+    } else {
+        None
+    }
+}
+
+/// Sync all three rolling channels, logging (rather than aborting) a failure
+/// on any one of them so a broken nightly manifest doesn't block stable/beta.
+pub async fn sync_all(pool: &Pool, cache: &ChannelCache) -> Result<(), SyncError> {
+    let mut last_err = None;
+    for channel in CHANNELS {
+        if let Err(e) = sync_channel(pool, cache, channel).await {
+            error!("failed to sync {channel} channel: {e}");
+            last_err = Some(e);
+        }
+    }
+    match last_err {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Spawn a background task that re-runs [`sync_all`] on a fixed interval for
+/// the lifetime of the actix runtime, so the DB stays fresh without an
+/// operator re-running the CLI subcommand by hand.
+pub fn spawn_periodic(pool: Pool, cache: actix_web::web::Data<ChannelCache>, interval: Duration) {
+    actix_web::rt::spawn(async move {
+        let mut ticker = actix_web::rt::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = sync_all(&pool, &cache).await {
+                warn!("periodic channel sync failed: {e}");
+            }
+        }
+    });
+}